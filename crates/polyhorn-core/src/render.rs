@@ -8,6 +8,75 @@ use std::ops::DerefMut;
 use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 
+/// Returns the variant name of the given element, for use as a tracing
+/// field. This avoids pulling `Debug` onto `Element<P>` just for logging.
+fn element_variant<P>(element: &Element<P>) -> &'static str
+where
+    P: Platform + ?Sized,
+{
+    match element {
+        Element::Builtin(_) => "Builtin",
+        Element::Component(_) => "Component",
+        Element::Context(_) => "Context",
+        Element::Fragment(_) => "Fragment",
+        Element::String(_) => "String",
+    }
+}
+
+/// Returns a stable id for the given instance that can be used to correlate
+/// tracing spans and events across a re-render. This is derived from the
+/// instance's address, which is stable for the lifetime of the `Rc`.
+fn instance_id<P>(instance: &Rc<Instance<P>>) -> usize
+where
+    P: Platform + ?Sized,
+{
+    Rc::as_ptr(instance) as usize
+}
+
+/// Shallow, structural equality between two elements, used by
+/// [`Render::rerender_edges`] to decide whether an edge can be skipped
+/// instead of re-rendered.
+///
+/// A `Builtin` carries its props behind an `Rc`, so rather than guessing at
+/// a field-by-field comparison of whatever a given builtin holds, we compare
+/// the `Rc`s themselves with `Rc::ptr_eq`: if the incoming element points at
+/// the exact same allocation as the one already mounted, its props are
+/// provably unchanged. This only ever under-approximates "unchanged" -- two
+/// calls that construct fresh, equal-but-distinct `Rc`s still compare as
+/// changed -- so it can't cause a real prop change to be silently skipped.
+///
+/// `Component` elements are always considered changed. The same `Rc::ptr_eq`
+/// trick would be just as safe to apply to them, but it's also just as
+/// unlikely to ever actually fire: an ordinary component render path
+/// constructs a fresh `Rc` per render, so `Rc::ptr_eq` would almost never be
+/// true in practice, making it dead weight rather than a real opt-in.
+/// Letting component authors actually opt into skipping would need a real
+/// mechanism -- an `Eq`-like bound on props, or a `should_update`-style hook
+/// on `Component` itself -- and `Component`'s definition isn't part of this
+/// crate's checkout to add one to. So for now component memoization stays
+/// user-inaccessible; `Context` elements are always considered changed too,
+/// since a context update needs to walk every descendant regardless (see
+/// `mark_subtree_dirty`).
+fn elements_equal<P>(a: &Element<P>, b: &Element<P>) -> bool
+where
+    P: Platform + ?Sized,
+{
+    match (a, b) {
+        (Element::String(a), Element::String(b)) => a == b,
+        (Element::Fragment(a), Element::Fragment(b)) => {
+            a.elements.len() == b.elements.len()
+                && a.elements
+                    .iter()
+                    .zip(&b.elements)
+                    .all(|(a, b)| elements_equal(a, b))
+        }
+        (Element::Builtin(a), Element::Builtin(b)) => {
+            Rc::ptr_eq(&a.builtin, &b.builtin) && elements_equal(&a.children, &b.children)
+        }
+        _ => false,
+    }
+}
+
 pub struct Render<P>
 where
     P: Platform + ?Sized,
@@ -19,6 +88,7 @@ where
 impl<P> Render<P>
 where
     P: Platform + ?Sized,
+    P::ContainerID: std::fmt::Debug,
 {
     fn new(renderer: Rc<Renderer<P>>) -> Render<P> {
         let buffer = renderer
@@ -30,10 +100,28 @@ where
         Render { renderer, buffer }
     }
 
+    #[tracing::instrument(
+        level = "trace",
+        skip_all,
+        fields(
+            instance = instance_id(instance),
+            container = ?instance.container(),
+            variant = "Builtin"
+        )
+    )]
     fn rerender_builtin(&mut self, instance: &Rc<Instance<P>>, element: ElementBuiltin<P>) {
         self.rerender_edges(instance, vec![*element.children]);
     }
 
+    #[tracing::instrument(
+        level = "trace",
+        skip_all,
+        fields(
+            instance = instance_id(instance),
+            container = ?instance.container(),
+            variant = "Component"
+        )
+    )]
     fn rerender_component(&mut self, instance: &Rc<Instance<P>>, element: ElementComponent<P>) {
         let (edges, effects) = {
             let mut memory = instance.memory_mut();
@@ -62,6 +150,49 @@ where
             )
         };
 
+        if self.renderer.is_suspended(instance_id(instance)) {
+            // This instance's render isn't ready yet (e.g. a `use_resource`
+            // hook returned a pending handle). We deliberately don't
+            // reconcile `edges` -- that would mount a half-finished subtree
+            // only to tear it down again once the suspending future
+            // resolves. Instead, we leave the instance's existing subtree
+            // mounted exactly as it is, and render the nearest ancestor
+            // `Suspense` boundary's fallback content in its place. Once
+            // `Renderer::resume` clears the suspended flag and schedules a
+            // re-render, this instance will reconcile normally again.
+            //
+            // Crucially, the fallback is reconciled into its own instance
+            // (one per boundary, created lazily and reused across
+            // suspensions) rather than through `rerender_edges(&boundary,
+            // ...)` directly: the boundary's own memory table holds the
+            // *real* content's edges, and mark-and-sweep reconciliation
+            // would remove/unmount every one of them the moment fallback
+            // markup -- which is structurally unrelated -- became current,
+            // tearing down the very subtree that's supposed to resume later.
+            if let Some(boundary) = self.renderer.nearest_boundary(instance) {
+                let boundary_id = instance_id(&boundary);
+
+                if let Some(fallback) = self.renderer.boundary_fallback(boundary_id) {
+                    let fragment = Element::Fragment(ElementFragment { elements: fallback });
+
+                    if let Some(fallback_instance) = self.renderer.fallback_instance(boundary_id) {
+                        fallback_instance.memory_mut().deref_mut().update(fragment);
+                        self.rerender(&fallback_instance);
+                    } else {
+                        let container = boundary.container().clone();
+                        let fallback_instance = self.render(Some(boundary.clone()), fragment, container);
+                        self.renderer.set_fallback_instance(boundary_id, fallback_instance);
+                    }
+                }
+            }
+
+            for effect in effects {
+                effect(&mut self.buffer);
+            }
+
+            return;
+        }
+
         self.rerender_edges(instance, edges);
 
         // Finally, we apply the effects and we're done!
@@ -70,17 +201,50 @@ where
         }
     }
 
+    #[tracing::instrument(
+        level = "trace",
+        skip_all,
+        fields(
+            instance = instance_id(instance),
+            container = ?instance.container(),
+            variant = "Context"
+        )
+    )]
     fn rerender_context(&mut self, instance: &Rc<Instance<P>>, element: ElementContext<P>) {
         instance.context().insert_raw(element.value);
 
+        // The new context value may affect any descendant that consumes it,
+        // so every instance below this provider must be re-rendered even if
+        // its own element compares equal to the one it already has.
+        self.renderer.mark_subtree_dirty(instance);
+
         self.rerender_edges(instance, vec![*element.children])
     }
 
+    #[tracing::instrument(
+        level = "trace",
+        skip_all,
+        fields(
+            instance = instance_id(instance),
+            container = ?instance.container(),
+            variant = "Fragment"
+        )
+    )]
     fn rerender_fragment(&mut self, instance: &Rc<Instance<P>>, element: ElementFragment<P>) {
         self.rerender_edges(instance, element.elements)
     }
 
     fn rerender_edges(&mut self, instance: &Rc<Instance<P>>, edges: Vec<Element<P>>) {
+        let span = tracing::trace_span!(
+            "rerender_edges",
+            instance = instance_id(instance),
+            kept = tracing::field::Empty,
+            created = tracing::field::Empty,
+            removed = tracing::field::Empty,
+            skipped = tracing::field::Empty,
+        );
+        let _enter = span.enter();
+
         // let missing_edges = memory.keep_edges(edges.iter().map(|element| element.key()));
         let mut memory = instance.memory_mut();
         let memory = memory.deref_mut();
@@ -89,19 +253,39 @@ where
         // the set of keys of edges.
         let mut keys = memory.keys();
 
+        let mut kept = 0;
+        let mut created = 0;
+        let mut skipped = 0;
+
         for element in edges {
             let key = element.key();
 
             keys.remove(key);
 
             if let Some(existing) = memory.edge(key) {
+                // Positional memoization: if nothing marked this edge dirty
+                // (a direct state write or a context update somewhere above
+                // it) and the incoming element is indistinguishable from the
+                // one it already holds, there is nothing to do -- skip the
+                // re-render (and everything below it) entirely, so it issues
+                // zero commands.
+                let dirty = self.renderer.take_dirty(instance_id(existing));
+                let unchanged = !dirty && elements_equal(existing.memory_mut().element(), &element);
+
+                if unchanged {
+                    skipped += 1;
+                    continue;
+                }
+
                 // The edge already exists. We replace its element and issue a
                 // re-render.
+                kept += 1;
                 existing.memory_mut().deref_mut().update(element);
                 self.rerender(existing)
             } else {
                 // The edge does not yet exist. We issue a fresh render and store
                 // the resulting instance in the memory of this instance.
+                created += 1;
                 let key = key.clone();
                 let instance = self.render(
                     Some(instance.clone()),
@@ -114,18 +298,52 @@ where
 
         // Finally, we unmount all instances that correspond to edges that are
         // no longer present.
+        let removed = keys.len();
+
         for key in keys {
             if let Some(instance) = memory.remove_edge(&key) {
                 self.unmount(&instance);
             }
         }
+
+        span.record("kept", kept);
+        span.record("created", created);
+        span.record("removed", removed);
+        span.record("skipped", skipped);
+
+        tracing::trace!(kept, created, removed, skipped, "reconciled edges");
     }
 
+    #[tracing::instrument(
+        level = "trace",
+        skip_all,
+        fields(
+            instance = instance_id(instance),
+            container = ?instance.container(),
+            variant = tracing::field::Empty
+        )
+    )]
     fn unmount(&mut self, instance: &Rc<Instance<P>>) {
+        tracing::Span::current().record(
+            "variant",
+            element_variant(instance.memory_mut().deref_mut().element()),
+        );
+
         for edge in instance.memory_mut().edges() {
             self.unmount(&edge);
         }
 
+        // If this instance is (or was) a `Suspense` boundary, its fallback
+        // content lives in its own, separate instance (see
+        // `rerender_component`'s suspended branch) and needs to be unmounted
+        // in its own right -- it isn't reachable through `instance`'s own
+        // edges.
+        if let Some(fallback_instance) = self.renderer.take_fallback_instance(instance_id(instance)) {
+            self.unmount(&fallback_instance);
+        }
+
+        self.renderer.forget_instance(instance_id(instance));
+
         match instance.memory_mut().deref_mut().element() {
             Element::Builtin(_) => {
                 self.buffer.unmount(instance.container());
@@ -135,8 +353,18 @@ where
     }
 
     /// This function is called when re-rendering an existing instance.
+    #[tracing::instrument(
+        level = "trace",
+        skip_all,
+        fields(
+            instance = instance_id(instance),
+            container = ?instance.container(),
+            variant = tracing::field::Empty
+        )
+    )]
     pub fn rerender(&mut self, instance: &Rc<Instance<P>>) {
         let element = instance.memory_mut().element().clone();
+        tracing::Span::current().record("variant", element_variant(&element));
 
         match element {
             Element::Builtin(element) => self.rerender_builtin(instance, element),
@@ -149,6 +377,11 @@ where
 
     /// This function is called when rendering an element into a container for
     /// the first time.
+    #[tracing::instrument(
+        level = "trace",
+        skip_all,
+        fields(variant = element_variant(&element), container = ?in_container)
+    )]
     pub fn render(
         &mut self,
         parent: Option<Rc<Instance<P>>>,
@@ -193,11 +426,39 @@ where
     compositor: RefCell<P::Compositor>,
     bus: RefCell<P::Bus>,
     environment: Arc<Mutex<P::Environment>>,
+
+    /// Tracks which instances have been marked dirty since an edge pointing
+    /// to them was last reconciled, keyed by `instance_id`. An instance is dirty when a
+    /// `use_state` write scheduled a re-render directly on it, or when an
+    /// ancestor `ContextProvider` above it changed value. `rerender_edges`
+    /// consults (and clears) this to decide whether a clean, unchanged edge
+    /// can be skipped.
+    dirty: RefCell<std::collections::HashSet<usize>>,
+
+    /// Instances that are currently suspended, i.e. whose last render
+    /// signalled that it isn't ready (for example because a `use_resource`
+    /// hook returned a pending handle). `rerender_component` consults this
+    /// to render the nearest `Suspense` boundary's fallback instead of the
+    /// instance's real children.
+    suspended: RefCell<std::collections::HashSet<usize>>,
+
+    /// The fallback content registered by each currently mounted `Suspense`
+    /// boundary, keyed by the boundary instance's id. A boundary re-registers
+    /// its fallback on every render of its own.
+    boundaries: RefCell<std::collections::HashMap<usize, Vec<Element<P>>>>,
+
+    /// The instance currently rendering a boundary's fallback content, keyed
+    /// by the boundary instance's id. This is a separate instance (with its
+    /// own, independent memory table) rather than something reconciled
+    /// directly into the boundary's own edges, so that swapping fallback
+    /// content in and out never touches the boundary's real children.
+    fallbacks: RefCell<std::collections::HashMap<usize, Rc<Instance<P>>>>,
 }
 
 impl<P> Renderer<P>
 where
     P: Platform + ?Sized,
+    P::ContainerID: std::fmt::Debug,
 {
     /// This function returns a new reference counted renderer with the given
     /// compositor.
@@ -210,20 +471,197 @@ where
             compositor: RefCell::new(compositor),
             bus: RefCell::new(bus),
             environment: Arc::new(Mutex::new(environment)),
+            dirty: RefCell::new(std::collections::HashSet::new()),
+            suspended: RefCell::new(std::collections::HashSet::new()),
+            boundaries: RefCell::new(std::collections::HashMap::new()),
+            fallbacks: RefCell::new(std::collections::HashMap::new()),
         })
     }
 
+    /// Marks `instance` as suspended: its real children won't be reconciled
+    /// until [`Renderer::resume`] is called for it.
+    ///
+    /// Nothing in this tree's snapshot of `polyhorn_core` calls this yet --
+    /// that would be a `use_resource`/`use_suspense`-style hook, built on
+    /// `Manager`/`Link`, neither of whose definitions are present here (this
+    /// crate's checkout is just this file; there's no `component.rs`,
+    /// `manager.rs`, `link.rs` or even a `lib.rs` to hang a new hook
+    /// module's `mod` declaration off of). The contract such a hook needs to
+    /// follow, once those land: call `renderer.suspend(instance)` the first
+    /// time its resource isn't ready, hold on to the `Rc<Renderer<P>>` and
+    /// `Rc<Instance<P>>` it was given (e.g. via `Link`, the way `use_state`'s
+    /// setter must already capture enough to call `Renderer::rerender`), and
+    /// call `renderer.resume(instance)` from wherever the underlying future
+    /// actually resolves.
+    pub fn suspend(self: &Rc<Self>, instance: &Rc<Instance<P>>) {
+        self.suspended.borrow_mut().insert(instance_id(instance));
+    }
+
+    /// Clears the suspended flag for `instance` and schedules a normal
+    /// re-render for it, so that it picks back up exactly where it left off.
+    /// See [`Renderer::suspend`] for the hook contract this pairs with.
+    ///
+    /// If `instance`'s nearest `Suspense` boundary has a fallback instance
+    /// mounted, it's unmounted as part of the same render pass that resumes
+    /// `instance`, so the fallback content doesn't linger behind the real
+    /// content once it reconciles back in. Note this eagerly tears down the
+    /// *boundary's* fallback instance rather than tracking a per-boundary
+    /// count of still-suspended descendants, so if a boundary has more than
+    /// one suspended descendant, resuming one will hide the fallback even
+    /// while another is still pending -- an acceptable simplification for a
+    /// single-descendant boundary, but worth revisiting if that's a real use
+    /// case.
+    pub fn resume(self: &Rc<Self>, instance: &Rc<Instance<P>>) {
+        self.suspended.borrow_mut().remove(&instance_id(instance));
+        self.mark_dirty(instance);
+
+        let boundary = self.nearest_boundary(instance);
+        let renderer = self.clone();
+        let instance = instance.clone();
+        let span = tracing::Span::current();
+
+        self.bus.borrow().queue_retain(async move {
+            let _enter = span.enter();
+            let mut render = Render::new(renderer.clone());
+
+            if let Some(boundary) = boundary {
+                if let Some(fallback_instance) = renderer.take_fallback_instance(instance_id(&boundary)) {
+                    render.unmount(&fallback_instance);
+                }
+            }
+
+            render.rerender(&instance);
+            render.buffer.commit();
+        });
+    }
+
+    fn is_suspended(&self, id: usize) -> bool {
+        self.suspended.borrow().contains(&id)
+    }
+
+    /// Returns the instance currently rendering `boundary_id`'s fallback
+    /// content, if one has been created yet.
+    fn fallback_instance(&self, boundary_id: usize) -> Option<Rc<Instance<P>>> {
+        self.fallbacks.borrow().get(&boundary_id).cloned()
+    }
+
+    /// Registers `instance` as the one rendering `boundary_id`'s fallback
+    /// content.
+    fn set_fallback_instance(&self, boundary_id: usize, instance: Rc<Instance<P>>) {
+        self.fallbacks.borrow_mut().insert(boundary_id, instance);
+    }
+
+    /// Removes and returns the instance rendering `boundary_id`'s fallback
+    /// content, if one exists, so the caller can unmount it.
+    fn take_fallback_instance(&self, boundary_id: usize) -> Option<Rc<Instance<P>>> {
+        self.fallbacks.borrow_mut().remove(&boundary_id)
+    }
+
+    /// Registers (or updates) the fallback content shown while any
+    /// descendant of `instance` is suspended.
+    ///
+    /// Like [`Renderer::suspend`], nothing calls this yet -- that would be a
+    /// `Suspense` component, whose `render` would need to call
+    /// `manager.renderer().register_boundary(instance, fallback_children)`
+    /// (or equivalent) on every render with its current fallback, then
+    /// return its real children unchanged the rest of the time; `rerender_component`
+    /// already does the other half (swapping in the nearest boundary's
+    /// fallback via [`Renderer::nearest_boundary`] whenever a descendant is
+    /// suspended). Writing the actual component is blocked on the same
+    /// missing `Component`/`Manager` definitions as `suspend` above --
+    /// without them, a hand-written `Component` impl would be guessing at
+    /// its own trait signature.
+    pub fn register_boundary(&self, instance: &Rc<Instance<P>>, fallback: Vec<Element<P>>) {
+        self.boundaries
+            .borrow_mut()
+            .insert(instance_id(instance), fallback);
+    }
+
+    fn boundary_fallback(&self, id: usize) -> Option<Vec<Element<P>>> {
+        self.boundaries.borrow().get(&id).cloned()
+    }
+
+    /// Walks up from `instance` to find the nearest ancestor that has
+    /// registered itself as a `Suspense` boundary. Because this always stops
+    /// at the first match, nested boundaries only ever catch suspensions
+    /// raised by their own descendants. Relies on `Instance::parent`, which
+    /// mirrors the `parent` that `Render::render` already threads through
+    /// `Instance::new`.
+    fn nearest_boundary(&self, instance: &Rc<Instance<P>>) -> Option<Rc<Instance<P>>> {
+        let mut current = instance.parent();
+
+        while let Some(candidate) = current {
+            if self.boundaries.borrow().contains_key(&instance_id(&candidate)) {
+                return Some(candidate);
+            }
+
+            current = candidate.parent();
+        }
+
+        None
+    }
+
+    /// Drops all renderer-level bookkeeping for an instance that is being
+    /// unmounted -- its dirty flag as well as any suspense bookkeeping --
+    /// so none of it leaks for the lifetime of the renderer.
+    fn forget_instance(&self, id: usize) {
+        self.dirty.borrow_mut().remove(&id);
+        self.suspended.borrow_mut().remove(&id);
+        self.boundaries.borrow_mut().remove(&id);
+    }
+
+    /// Marks a single instance dirty, so that the next time an edge pointing
+    /// to it is reconciled, it is re-rendered even if its element is
+    /// unchanged.
+    fn mark_dirty(&self, instance: &Rc<Instance<P>>) {
+        self.dirty.borrow_mut().insert(instance_id(instance));
+    }
+
+    /// Marks the given instance and all of its descendants dirty. Used when
+    /// a `ContextProvider`'s value changes, since we don't track which
+    /// specific descendants consume it.
+    fn mark_subtree_dirty(&self, instance: &Rc<Instance<P>>) {
+        self.mark_dirty(instance);
+
+        for edge in instance.memory_mut().edges() {
+            self.mark_subtree_dirty(&edge);
+        }
+    }
+
+    /// Returns whether the given instance was dirty, clearing the flag.
+    fn take_dirty(&self, id: usize) -> bool {
+        self.dirty.borrow_mut().remove(&id)
+    }
+
+    #[tracing::instrument(
+        level = "trace",
+        skip_all,
+        fields(
+            instance = instance_id(instance),
+            container = ?instance.container(),
+            variant = element_variant(instance.memory_mut().element())
+        )
+    )]
     pub fn rerender(self: &Rc<Self>, instance: &Rc<Instance<P>>) {
+        self.mark_dirty(instance);
+
         let renderer = self.clone();
         let instance = instance.clone();
+        let span = tracing::Span::current();
 
         self.bus.borrow().queue_retain(async move {
+            let _enter = span.enter();
             let mut render = Render::new(renderer);
             render.rerender(&instance);
             render.buffer.commit();
         });
     }
 
+    #[tracing::instrument(
+        level = "trace",
+        skip_all,
+        fields(variant = element_variant(&element), container = ?container)
+    )]
     pub fn render(
         self: &Rc<Self>,
         element: Element<P>,
@@ -250,6 +688,7 @@ pub fn render<F, P>(element: F, container: P::Container) -> Disposable
 where
     F: FnOnce() -> Element<P> + Send + 'static,
     P: Platform + ?Sized,
+    P::ContainerID: std::fmt::Debug,
 {
     P::with_compositor(
         container,
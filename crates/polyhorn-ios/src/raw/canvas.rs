@@ -0,0 +1,138 @@
+//! Native half of the `Canvas` builtin. The platform-agnostic draw list that
+//! `use_canvas` (in `polyhorn_ui`) lets a component record into is defined in
+//! `polyhorn_ui::canvas`; this module is what turns it into a registered,
+//! paintable container, modeled on the same paint-task architecture the
+//! draw list's own docs describe: the component side records a list of
+//! drawing messages, and `Apply` flushes that list to the native drawing
+//! context (CoreGraphics) only when it has actually changed since the
+//! previous frame.
+//!
+//! This is the iOS half only. An Android `Canvas` builtin would live under
+//! `polyhorn-android-sys`, flushing the same `DrawList` to that platform's
+//! drawing context via its own `Apply` impl instead of CoreGraphics -- but
+//! `polyhorn-android-sys`'s checkout here is just its `lib.rs`, declaring
+//! `activity`/`bridge`/`context`/`logger`/`reference`/`runnable`/`thread`/
+//! `view` modules none of which exist as files, so there's no bridge module
+//! to hang a Canvas implementation off of in this tree. Android support is
+//! an explicit gap, not an oversight.
+
+use std::sync::Mutex;
+
+use polyhorn_ui::canvas::DrawList;
+
+use super::{Apply, Builtin, Environment, OpaqueContainer};
+
+/// The `Canvas` builtin. An `ElementBuiltin` carries one of these through
+/// its `builtin` field, the same way it would for any other built-in
+/// container; `instantiate` creates its native container, and `Apply`
+/// remembers the draw list it flushed for the previous frame so it can skip
+/// repainting an unchanged one.
+pub struct CanvasBuiltin {
+    previous: Mutex<Option<DrawList>>,
+}
+
+impl CanvasBuiltin {
+    /// Returns a new, not-yet-painted canvas builtin.
+    pub fn new() -> CanvasBuiltin {
+        CanvasBuiltin {
+            previous: Mutex::new(None),
+        }
+    }
+}
+
+impl Default for CanvasBuiltin {
+    fn default() -> Self {
+        CanvasBuiltin::new()
+    }
+}
+
+impl Builtin for CanvasBuiltin {
+    // Note: `instantiate` only receives `&mut Environment`, not the
+    // `Compositor` that assigns `ContainerID`s (see `Compositor::track`);
+    // like every other builtin, the container this returns gets its id from
+    // the `CommandBuffer::mount` call that invokes this closure, not from
+    // `instantiate` itself.
+    //
+    // This still hands back whatever `OpaqueContainer::canvas` builds, which
+    // is not yet a leaf built with `Layout::leaf_with_measure` -- the
+    // container must participate in layout as a leaf whose `MeasureFunc`
+    // reports an intrinsic size, but `OpaqueContainer::canvas` is the one
+    // that would have to call `Layout::leaf_with_measure` instead of
+    // `Layout::leaf` to build it that way, and `OpaqueContainer`'s
+    // definition (`raw::container`) isn't part of this crate's checkout to
+    // change. Until that wiring exists, a mounted `Canvas` reports a zero
+    // intrinsic size to its parent's layout, same as any other leaf.
+    fn instantiate(&self, environment: &mut Environment) -> OpaqueContainer {
+        OpaqueContainer::canvas(environment)
+    }
+}
+
+impl Apply<DrawList> for CanvasBuiltin {
+    /// Flushes `next` to the container's drawing context if it differs from
+    /// the draw list flushed for the previous frame, via the same
+    /// comparison `flush_if_changed` runs, then remembers it as the new
+    /// previous frame.
+    fn apply(
+        &self,
+        containers: &mut [&mut OpaqueContainer],
+        _environment: &mut Environment,
+        next: &DrawList,
+    ) {
+        let mut previous = self.previous.lock().unwrap();
+        let flushed = flush_if_changed(previous.as_ref(), next.clone(), |draw_list| {
+            if let Some(container) = containers.first_mut() {
+                container.paint(draw_list);
+            }
+        });
+        *previous = Some(flushed);
+    }
+}
+
+/// Flushes `next` to `paint` if it differs from `previous`, then returns
+/// `next` so the caller can hold on to it as the new `previous` for the
+/// following frame.
+fn flush_if_changed<F>(previous: Option<&DrawList>, next: DrawList, mut paint: F) -> DrawList
+where
+    F: FnMut(&DrawList),
+{
+    if previous != Some(&next) {
+        paint(&next);
+    }
+
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polyhorn_ui::canvas::DrawCommand;
+    use polyhorn_ui::geometry::{Point, Size};
+
+    fn clear_rect() -> DrawCommand {
+        DrawCommand::ClearRect {
+            origin: Point { x: 0.0, y: 0.0 },
+            size: Size {
+                width: 1.0,
+                height: 1.0,
+            },
+        }
+    }
+
+    #[test]
+    fn flush_if_changed_skips_identical_frames() {
+        let mut paints = 0;
+        let mut list = DrawList::new();
+        list.push(clear_rect());
+
+        let previous = flush_if_changed(None, list.clone(), |_| paints += 1);
+        assert_eq!(paints, 1, "first frame should always paint");
+
+        let previous = flush_if_changed(Some(&previous), list.clone(), |_| paints += 1);
+        assert_eq!(paints, 1, "an unchanged draw list should not repaint");
+
+        let mut changed = list;
+        changed.push(clear_rect());
+        let _ = flush_if_changed(Some(&previous), changed, |_| paints += 1);
+        assert_eq!(paints, 2, "a changed draw list should repaint");
+    }
+}
@@ -57,6 +57,22 @@ impl polyhorn_core::Compositor<Platform> for Compositor {
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct ContainerID(usize);
 
+impl ContainerID {
+    /// Returns the numeric value of this ID, for transports that need to
+    /// serialize it (see [`super::remote`]).
+    pub(crate) fn as_usize(&self) -> usize {
+        self.0
+    }
+
+    /// Reconstructs a `ContainerID` from a numeric value that was obtained
+    /// through [`ContainerID::as_usize`]. The caller is responsible for
+    /// ensuring that the id was actually issued by the corresponding
+    /// compositor.
+    pub(crate) fn from_usize(id: usize) -> ContainerID {
+        ContainerID(id)
+    }
+}
+
 /// Concrete implementation of a command buffer that can buffer commands before
 /// committing them to the compositor.
 pub struct CommandBuffer {
@@ -70,6 +86,7 @@ impl polyhorn_core::CommandBuffer<Platform> for CommandBuffer {
         F: FnOnce(&mut OpaqueContainer, &mut Environment) -> OpaqueContainer + Send + 'static,
     {
         let id = self.compositor.next_id();
+        tracing::trace!(container = ?id, parent = ?parent_id, "queued Mount command");
         self.commands
             .push(Command::Mount(id, parent_id, Box::new(initializer)));
         id
@@ -79,11 +96,13 @@ impl polyhorn_core::CommandBuffer<Platform> for CommandBuffer {
     where
         F: FnOnce(&mut [&mut OpaqueContainer], &mut Environment) + Send + 'static,
     {
+        tracing::trace!(containers = ?ids, "queued Mutate command");
         self.commands
             .push(Command::Mutate(ids.to_owned(), Box::new(mutator)));
     }
 
     fn unmount(&mut self, id: ContainerID) {
+        tracing::trace!(container = ?id, "queued Unmount command");
         self.commands.push(Command::Unmount(id));
     }
 
@@ -94,15 +113,31 @@ impl polyhorn_core::CommandBuffer<Platform> for CommandBuffer {
         });
     }
 
+    #[tracing::instrument(level = "trace", skip_all, fields(commands = self.commands.len()))]
     fn commit(mut self) {
         let commands = std::mem::take(&mut self.commands);
 
         let layout_tree = self.compositor.layout_tree.clone();
+        let span = tracing::Span::current();
 
         self.compositor.buffer.with(move |state| {
+            let _enter = span.enter();
+
             // Apply each command to this state.
             let mut environment = Environment::new(layout_tree.clone());
             for command in commands {
+                match &command {
+                    Command::Mount(id, parent_id, _) => {
+                        tracing::trace!(container = ?id, parent = ?parent_id, "applying Mount command")
+                    }
+                    Command::Mutate(ids, _) => {
+                        tracing::trace!(containers = ?ids, "applying Mutate command")
+                    }
+                    Command::Unmount(id) => {
+                        tracing::trace!(container = ?id, "applying Unmount command")
+                    }
+                }
+
                 state.process(&mut environment, command);
             }
         });
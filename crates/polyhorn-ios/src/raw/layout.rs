@@ -116,6 +116,26 @@ impl Layout {
         Layout { layouter, node }
     }
 
+    /// Creates a new leaf in the layout tree whose intrinsic size is
+    /// reported by the given measure function, instead of defaulting to
+    /// zero. Intended for builtins such as `Canvas` that need to participate
+    /// in layout as a leaf but still have a natural size of their own --
+    /// though wiring `Canvas` up to it specifically needs a change in
+    /// `raw::container` (see the note on `CanvasBuiltin::instantiate`) that
+    /// this crate's checkout doesn't include, so no caller uses this yet.
+    pub fn leaf_with_measure<F>(layouter: Arc<RwLock<Layouter>>, measure: F) -> Layout
+    where
+        F: Fn(Size) -> Size + Send + Sync + 'static,
+    {
+        let node = layouter
+            .write()
+            .unwrap()
+            .flexbox_mut()
+            .new_leaf(Default::default(), MeasureFunc::Boxed(Box::new(measure)));
+
+        Layout { layouter, node }
+    }
+
     /// Returns a shared reference to the layout tree.
     pub fn layouter(&self) -> &Arc<RwLock<Layouter>> {
         &self.layouter
@@ -3,23 +3,29 @@
 mod animator;
 mod apply;
 mod builtin;
+mod canvas;
 mod component;
 mod compositor;
 mod container;
 mod convert;
 mod environment;
+mod layout;
 mod markup;
 mod platform;
 mod queue;
+mod remote;
 
 pub use animator::{AnimationHandle, Animator};
 pub use apply::Apply;
 pub use builtin::Builtin;
+pub use canvas::CanvasBuiltin;
 pub use component::{Component, OpaqueComponent};
 pub use compositor::{CommandBuffer, Compositor, ContainerID};
 pub use container::{Container, OpaqueContainer};
 pub use convert::Convert;
 pub use environment::Environment;
+pub use layout::{Layout, Layouter};
 pub use markup::attributed_string;
 pub use platform::Platform;
 pub use queue::QueueBound;
+pub use remote::{ContainerKind, RemoteClient, RemoteCommand, RemoteCommandBuffer, Transport};
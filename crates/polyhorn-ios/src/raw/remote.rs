@@ -0,0 +1,334 @@
+//! Remote command protocol.
+//!
+//! `Command<Platform>`'s `Mount` and `Mutate` variants carry `FnOnce`
+//! closures that capture native types directly, so they can't cross a
+//! process or thread boundary. This module defines a parallel, serializable
+//! command set -- [`RemoteCommand`] -- plus a compact binary encoding for it,
+//! so that a reconciler running in a host process can ship a stream of
+//! frames to a thin native client over a [`Transport`] (a websocket, a pipe,
+//! or anything else the embedder wants to plug in). The client decodes each
+//! frame and replays it against its own `Compositor`, mirroring a
+//! LiveView-style split between where the tree is computed and where it is
+//! displayed.
+//!
+//! This intentionally mirrors the shape of `Command`/`CommandBuffer` rather
+//! than reusing them: a `Command::Mount` closure can call arbitrary native
+//! APIs, but a `RemoteCommand::Mount` can only carry the information needed
+//! to pick a `ContainerKind`, which is enough for a remote client to
+//! reconstruct the shape of the same tree.
+//!
+//! Style mutations (`Command::Mutate`) are deliberately **not** part of this
+//! wire protocol yet. `ViewStyle`'s definition isn't part of this crate's
+//! checkout, so there's no way to write a real field-level encoding for it
+//! here, and round-tripping it through its `Debug` representation (an
+//! earlier version of this module did exactly that) only looks like it
+//! works: `Debug` text can be produced but never parsed back, so every
+//! decoded `Mutate` frame would have to be dropped anyway, while `encode`
+//! quietly went on building frames no client could ever apply. Shipping a
+//! command variant that can be constructed and "encoded" but never
+//! correctly decoded is worse than not having it, so it's cut from scope
+//! here; a remote client only gets `Mount`/`Unmount`, enough to track the
+//! shape of the tree for layout purposes (see `RemoteClient`), until
+//! `ViewStyle` has a real encoding to build this on top of.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use super::{ContainerID, Layout, Layouter};
+
+/// A destination that a stream of encoded [`RemoteCommand`] frames can be
+/// written to. Implementations are expected to preserve frame boundaries and
+/// ordering, but are otherwise free to ship them however they like (a
+/// websocket message per frame, a length-prefixed pipe, etc).
+pub trait Transport {
+    /// Sends a single encoded frame to the client.
+    fn send(&mut self, frame: Vec<u8>);
+}
+
+/// The kind of native container that a client should instantiate for a
+/// [`RemoteCommand::Mount`]. This mirrors the set of builtins that
+/// `Builtin::instantiate` knows how to construct, without requiring the
+/// closure that a local `Command::Mount` carries.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum ContainerKind {
+    View = 0,
+    Text = 1,
+    Image = 2,
+    ScrollView = 3,
+    Canvas = 4,
+}
+
+impl ContainerKind {
+    fn to_u8(self) -> u8 {
+        self as u8
+    }
+
+    fn from_u8(tag: u8) -> Option<ContainerKind> {
+        match tag {
+            0 => Some(ContainerKind::View),
+            1 => Some(ContainerKind::Text),
+            2 => Some(ContainerKind::Image),
+            3 => Some(ContainerKind::ScrollView),
+            4 => Some(ContainerKind::Canvas),
+            _ => None,
+        }
+    }
+}
+
+/// A serializable counterpart to `Command<Platform>`.
+#[derive(Clone, Debug)]
+pub enum RemoteCommand {
+    /// Instantiate a new container of the given kind as a child of
+    /// `parent_id`, tracked under `id`.
+    Mount {
+        id: ContainerID,
+        parent_id: ContainerID,
+        kind: ContainerKind,
+    },
+
+    /// Remove the given container from the native view hierarchy.
+    Unmount { id: ContainerID },
+}
+
+const TAG_MOUNT: u8 = 0;
+const TAG_UNMOUNT: u8 = 2;
+
+impl RemoteCommand {
+    /// Encodes this command into a compact binary frame. The format is a
+    /// one-byte tag followed by the fields of the corresponding variant,
+    /// with `ContainerID`s encoded as little-endian `u64`s.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut frame = Vec::new();
+
+        match self {
+            RemoteCommand::Mount {
+                id,
+                parent_id,
+                kind,
+            } => {
+                frame.push(TAG_MOUNT);
+                encode_id(&mut frame, *id);
+                encode_id(&mut frame, *parent_id);
+                frame.push(kind.to_u8());
+            }
+            RemoteCommand::Unmount { id } => {
+                frame.push(TAG_UNMOUNT);
+                encode_id(&mut frame, *id);
+            }
+        }
+
+        frame
+    }
+
+    /// Decodes a frame that was produced by [`RemoteCommand::encode`].
+    /// Returns `None` if the frame is truncated or carries an unknown tag.
+    pub fn decode(frame: &[u8]) -> Option<RemoteCommand> {
+        let mut cursor = 0;
+        let tag = *frame.get(cursor)?;
+        cursor += 1;
+
+        match tag {
+            TAG_MOUNT => {
+                let id = decode_id(frame, &mut cursor)?;
+                let parent_id = decode_id(frame, &mut cursor)?;
+                let kind = ContainerKind::from_u8(*frame.get(cursor)?)?;
+
+                Some(RemoteCommand::Mount {
+                    id,
+                    parent_id,
+                    kind,
+                })
+            }
+            TAG_UNMOUNT => {
+                let id = decode_id(frame, &mut cursor)?;
+                Some(RemoteCommand::Unmount { id })
+            }
+            _ => None,
+        }
+    }
+}
+
+fn encode_id(frame: &mut Vec<u8>, id: ContainerID) {
+    frame.extend_from_slice(&(id.as_usize() as u64).to_le_bytes());
+}
+
+fn decode_id(frame: &[u8], cursor: &mut usize) -> Option<ContainerID> {
+    let value = decode_u64(frame, cursor)?;
+    Some(ContainerID::from_usize(value as usize))
+}
+
+fn decode_u64(frame: &[u8], cursor: &mut usize) -> Option<u64> {
+    let bytes = frame.get(*cursor..*cursor + 8)?;
+    *cursor += 8;
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+/// Buffers [`RemoteCommand`]s and flushes them to a [`Transport`] as encoded
+/// frames. This plays the same role for a remote host that `CommandBuffer`
+/// plays locally, but deliberately does not implement
+/// `polyhorn_core::CommandBuffer<Platform>`: that trait's `mount`/`mutate`
+/// take `FnOnce` closures that build and mutate a real `OpaqueContainer`, and
+/// there is no way to turn such a closure into a `RemoteCommand` without
+/// running it against a real container and environment first (which is
+/// exactly what the closure can't be trusted to do outside of the render
+/// thread it captured state from). Making `Renderer<Platform>` target a
+/// remote transport instead of the local `Compositor` would need a second
+/// `Platform` implementation with its own `CommandBuffer`/`Compositor`/
+/// `Environment`/`Container` types built around `RemoteCommand` from the
+/// start, which is a bigger change than this module can take on by itself
+/// (and one this tree's snapshot doesn't include `Platform`'s definition
+/// for). What this type -- and [`RemoteClient`] on the decoding side -- can
+/// do instead is speak the wire format: a host-side adapter that already has
+/// a real `OpaqueContainer` in hand (e.g. from within a `CommandBuffer::mount`
+/// closure it's already running) can translate that into calls against
+/// `RemoteCommandBuffer` directly.
+pub struct RemoteCommandBuffer<T> {
+    transport: T,
+    commands: Vec<RemoteCommand>,
+}
+
+impl<T> RemoteCommandBuffer<T>
+where
+    T: Transport,
+{
+    /// Returns a new, empty remote command buffer that writes to the given
+    /// transport.
+    pub fn new(transport: T) -> RemoteCommandBuffer<T> {
+        RemoteCommandBuffer {
+            transport,
+            commands: vec![],
+        }
+    }
+
+    /// Queues a mount of the given kind as a child of `parent_id`.
+    pub fn mount(&mut self, id: ContainerID, parent_id: ContainerID, kind: ContainerKind) {
+        self.commands.push(RemoteCommand::Mount {
+            id,
+            parent_id,
+            kind,
+        });
+    }
+
+    /// Queues an unmount of the given container.
+    pub fn unmount(&mut self, id: ContainerID) {
+        self.commands.push(RemoteCommand::Unmount { id });
+    }
+
+    /// Encodes and ships every queued command to the transport, in order.
+    pub fn commit(mut self) {
+        for command in std::mem::take(&mut self.commands) {
+            self.transport.send(command.encode());
+        }
+    }
+}
+
+/// Replays decoded [`RemoteCommand`]s against a client-side layout tree,
+/// keyed by the same `ContainerID`s the host assigned.
+///
+/// This is deliberately scoped to layout rather than real native views:
+/// wiring a replayed `Mount` up to CoreGraphics would need `OpaqueContainer`,
+/// which (like the rest of `raw::container`) isn't
+/// something outside that module can construct. What a `RemoteClient` does
+/// is track the shape of the remote tree: every mounted container becomes a
+/// layout leaf whose measure function reports a zero size, whatever its
+/// `ContainerKind`. It never recomputes layout itself, and it does not add
+/// any node to the layouter's roots.
+pub struct RemoteClient {
+    layouter: Arc<RwLock<Layouter>>,
+    nodes: HashMap<ContainerID, Layout>,
+}
+
+impl RemoteClient {
+    /// Returns a new client backed by the given layout tree.
+    pub fn new(layouter: Arc<RwLock<Layouter>>) -> RemoteClient {
+        RemoteClient {
+            layouter,
+            nodes: HashMap::new(),
+        }
+    }
+
+    /// Applies a single decoded command to this client's layout tree.
+    pub fn apply(&mut self, command: RemoteCommand) {
+        match command {
+            RemoteCommand::Mount { id, parent_id, .. } => {
+                let layout = Layout::leaf(self.layouter.clone());
+
+                if let Some(parent) = self.nodes.get(&parent_id) {
+                    self.layouter
+                        .write()
+                        .unwrap()
+                        .add_child(parent.node(), layout.node());
+                }
+
+                self.nodes.insert(id, layout);
+            }
+            RemoteCommand::Unmount { id } => {
+                if let Some(layout) = self.nodes.remove(&id) {
+                    self.layouter.write().unwrap().remove(layout.node());
+                }
+            }
+        }
+    }
+
+    /// Returns the `(width, height)` of `id`'s node as of the layouter's most
+    /// recent layout computation, for shipping back to the host. This reads
+    /// the current layout and does not recompute it; how the value travels
+    /// back is up to whatever `Transport` the embedder has flowing in the
+    /// other direction.
+    pub fn measured_size(&self, id: ContainerID) -> Option<(f32, f32)> {
+        self.nodes.get(&id).map(|layout| {
+            let layout = layout.current();
+            (layout.size.width, layout.size.height)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mount_and_unmount_round_trip_through_encode_decode() {
+        let mount = RemoteCommand::Mount {
+            id: ContainerID::from_usize(1),
+            parent_id: ContainerID::from_usize(0),
+            kind: ContainerKind::Text,
+        };
+        let decoded = RemoteCommand::decode(&mount.encode()).unwrap();
+        match decoded {
+            RemoteCommand::Mount {
+                id,
+                parent_id,
+                kind,
+            } => {
+                assert_eq!(id, ContainerID::from_usize(1));
+                assert_eq!(parent_id, ContainerID::from_usize(0));
+                assert_eq!(kind, ContainerKind::Text);
+            }
+            _ => panic!("expected a Mount command"),
+        }
+
+        let unmount = RemoteCommand::Unmount {
+            id: ContainerID::from_usize(1),
+        };
+        let decoded = RemoteCommand::decode(&unmount.encode()).unwrap();
+        match decoded {
+            RemoteCommand::Unmount { id } => assert_eq!(id, ContainerID::from_usize(1)),
+            _ => panic!("expected an Unmount command"),
+        }
+    }
+
+    #[test]
+    fn decode_fails_on_truncated_frames() {
+        let mount = RemoteCommand::Mount {
+            id: ContainerID::from_usize(1),
+            parent_id: ContainerID::from_usize(0),
+            kind: ContainerKind::View,
+        };
+        let mut frame = mount.encode();
+        frame.truncate(frame.len() - 1);
+
+        assert!(RemoteCommand::decode(&frame).is_none());
+    }
+}
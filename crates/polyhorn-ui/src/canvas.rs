@@ -0,0 +1,155 @@
+//! Platform-agnostic data types for the `Canvas` builtin's immediate-mode
+//! drawing surface. The draw list itself is just a diffable list of drawing
+//! messages built from the same geometry types the rest of `polyhorn_ui`
+//! uses; the native side that actually paints it (see
+//! `polyhorn_ios::raw::canvas`) lives in each platform crate instead, so
+//! that this module -- and `use_canvas`, which hands out a [`CanvasHandle`]
+//! -- can be shared across platforms.
+
+use std::sync::{Arc, Mutex};
+
+use crate::geometry::{Point, Size};
+
+/// A single drawing instruction recorded by a canvas's draw list. Geometry
+/// reuses the same types as the rest of `polyhorn_ui` so that a canvas
+/// composes naturally with the surrounding style system; colors are packed
+/// `0xRRGGBBAA` so that this module doesn't need to depend on a richer color
+/// type.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DrawCommand {
+    FillRect {
+        origin: Point<f32>,
+        size: Size<f32>,
+        color: u32,
+    },
+    StrokeRect {
+        origin: Point<f32>,
+        size: Size<f32>,
+        color: u32,
+        width: f32,
+    },
+    ClearRect {
+        origin: Point<f32>,
+        size: Size<f32>,
+    },
+    FillPath {
+        points: Vec<Point<f32>>,
+        color: u32,
+    },
+    DrawText {
+        origin: Point<f32>,
+        text: String,
+        color: u32,
+    },
+}
+
+/// The complete set of drawing messages for one frame of a canvas. Comparing
+/// two draw lists with `==` is how the native `Canvas` builtin's `Apply`
+/// decides whether an unchanged canvas can skip repainting.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DrawList {
+    commands: Vec<DrawCommand>,
+}
+
+impl DrawList {
+    /// Returns an empty draw list.
+    pub fn new() -> DrawList {
+        DrawList::default()
+    }
+
+    /// Appends a drawing instruction to this list.
+    pub fn push(&mut self, command: DrawCommand) {
+        self.commands.push(command);
+    }
+
+    /// Returns the recorded drawing instructions, in the order they were
+    /// pushed.
+    pub fn commands(&self) -> &[DrawCommand] {
+        &self.commands
+    }
+
+    /// Empties this draw list, e.g. before a component re-records a frame.
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+}
+
+/// A cheap-to-clone handle into a canvas's draw list. This is what
+/// `use_canvas` hands back, so that effects can enqueue draw commands
+/// without going through a full reconcile.
+#[derive(Clone)]
+pub struct CanvasHandle {
+    draw_list: Arc<Mutex<DrawList>>,
+}
+
+impl CanvasHandle {
+    /// Returns a new handle around an empty draw list.
+    pub fn new() -> CanvasHandle {
+        CanvasHandle {
+            draw_list: Arc::new(Mutex::new(DrawList::new())),
+        }
+    }
+
+    /// Enqueues a drawing instruction for the next flush.
+    pub fn push(&self, command: DrawCommand) {
+        self.draw_list.lock().unwrap().push(command);
+    }
+
+    /// Clears the draw list, e.g. at the start of a new frame.
+    pub fn clear(&self) {
+        self.draw_list.lock().unwrap().clear();
+    }
+
+    /// Returns a clone of the draw list as it currently stands, for diffing
+    /// against the list that was flushed for the previous frame.
+    pub fn snapshot(&self) -> DrawList {
+        self.draw_list.lock().unwrap().clone()
+    }
+}
+
+impl Default for CanvasHandle {
+    fn default() -> Self {
+        CanvasHandle::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clear_rect() -> DrawCommand {
+        DrawCommand::ClearRect {
+            origin: Point { x: 0.0, y: 0.0 },
+            size: Size {
+                width: 1.0,
+                height: 1.0,
+            },
+        }
+    }
+
+    #[test]
+    fn snapshot_reflects_pushes_and_clears() {
+        let handle = CanvasHandle::new();
+        assert_eq!(handle.snapshot(), DrawList::new());
+
+        handle.push(clear_rect());
+        assert_eq!(handle.snapshot().commands(), &[clear_rect()]);
+
+        handle.clear();
+        assert_eq!(handle.snapshot(), DrawList::new());
+    }
+
+    #[test]
+    fn equal_draw_lists_compare_equal() {
+        let mut a = DrawList::new();
+        a.push(clear_rect());
+
+        let mut b = DrawList::new();
+        b.push(clear_rect());
+
+        assert_eq!(a, b);
+
+        b.push(clear_rect());
+        assert_ne!(a, b);
+    }
+}
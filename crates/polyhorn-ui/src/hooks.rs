@@ -0,0 +1,23 @@
+//! Hooks that return `polyhorn_ui`-specific handles, so they don't fit
+//! alongside the platform-agnostic hooks in `polyhorn_core`.
+
+use polyhorn_core::use_reference;
+
+use crate::canvas::CanvasHandle;
+
+/// Returns a handle to a draw list for a `Canvas`. Call `push`/`clear` on the
+/// returned handle (typically from an effect, so it doesn't run during
+/// render) to record drawing instructions. This hook does not connect the
+/// handle to a mounted `Canvas` builtin: the recorded list is painted only
+/// when its `snapshot()` is passed to that builtin's `Apply`, which repaints
+/// only when the list has changed since the previous frame.
+///
+/// The handle is created once, on the owning instance's first render, and
+/// held in that instance's own memory via `use_reference`: every later
+/// render of the same instance gets back a clone of that same handle (cheap,
+/// since a `CanvasHandle` is just an `Arc` around its draw list) rather than
+/// a fresh, empty one, so an effect that stashed the handle returned by a
+/// previous render still records into the same draw list.
+pub fn use_canvas() -> CanvasHandle {
+    use_reference(CanvasHandle::new).read().clone()
+}
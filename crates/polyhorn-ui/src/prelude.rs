@@ -4,4 +4,5 @@ pub use polyhorn_core::{
     use_async, use_context, use_effect, use_id, use_reference, use_state, with, ContextProvider,
 };
 
-pub use crate::hooks::use_safe_area_insets;
+pub use crate::canvas::{CanvasHandle, DrawCommand, DrawList};
+pub use crate::hooks::{use_canvas, use_safe_area_insets};